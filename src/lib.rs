@@ -135,8 +135,14 @@
 //!         assert_eq!(Ok(5), result);
 //!     }
 //! ```
+use std::any::Any;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::future::{Future};
+use std::time::Duration;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::marker::PhantomData;
 
 /// Abstract builder, a function constructing an async state function
 ///
@@ -168,16 +174,437 @@ pub enum Transit<'s, Composite,  Out, Err>
     Lift(Out),
 }
 
+/// Type-level coproduct of `Out` alternatives, so `Transit::Lift` is not hard-wired to a single
+/// parent's `BuilderPair` - a reusable sub-machine (like `Play`) can `Lift` a value of
+/// `Coproduct<BuilderPair<ParentA, ..>, Coproduct<BuilderPair<ParentB, ..>, CNil>>` and leave each
+/// parent's `init` to `Uninject` the one alternative it knows how to build, passing the rest through
+/// untouched. Built via recursive `LiftInto`/`Uninject` impls rather than per-arity ones, so there is
+/// no fixed alternative count to it; `coproduct_type!` only saves writing the nesting by hand.
+#[derive(Debug, Clone, Copy)]
+pub enum Coproduct<Head, Tail> {
+    /// The alternative at this position
+    Inl(Head),
+    /// Defer to the remaining alternatives
+    Inr(Tail),
+}
+
+/// The empty coproduct - no alternatives left, and so uninhabited; closes off the `Tail` of the
+/// innermost `Coproduct`.
+#[derive(Debug, Clone, Copy)]
+pub enum CNil {}
+
+/// Marks the alternative at the head of a `Coproduct` - the base case of `LiftInto`/`Uninject`.
+pub struct Here;
+
+/// Marks an alternative found by skipping the head and recursing into the `Coproduct`'s `Tail`.
+pub struct There<Index>(PhantomData<Index>);
+
+/// Inject `Self` into one alternative of the coproduct `Super`, analogous to `frunk`-style coproduct
+/// injection. `Index` disambiguates which alternative when more than one could structurally match;
+/// it is inferred, never named at the call site.
+///
+/// #Examples
+/// A `ping` sub-machine reusable under two unrelated parents: it is generic over `Data` so it can be
+/// instantiated as either parent's own `Composite<Data>` (per this crate's convention of a
+/// sub-machine sharing its parent's concrete `Composite` type, see the crate-level docs), and its
+/// `Lift` carries a `Coproduct` of both parents' `BuilderPair`s instead of being hard-wired to one.
+/// ```
+///     use async_hsm::{Composite, Transit, Builder, BuilderPair, Coproduct, CNil, LiftInto, Uninject, coproduct_type};
+///
+///     type Score = u32;
+///
+///     #[derive(Debug, Clone, PartialEq)]
+///     enum AppError { Failure }
+///
+///     struct AppData;
+///     struct AltData;
+///
+///     type AppComposite = Composite<AppData>;
+///     type AltComposite = Composite<AltData>;
+///     type AppBuilder = Builder<AppComposite, Score, Score, AppError>;
+///     type AltBuilder = Builder<AltComposite, Score, Score, AppError>;
+///
+///     // Every parent `ping` can lift into - add a third parent here without touching `ping` itself.
+///     type ParentLift = coproduct_type!(
+///         BuilderPair<AppComposite, Score, Score, AppError>,
+///         BuilderPair<AltComposite, Score, Score, AppError>
+///     );
+///
+///     static APP_DONE: AppBuilder = || |_comp, score| Box::pin(async move { Ok(Transit::Lift(score)) });
+///     static ALT_DONE: AltBuilder = || |_comp, score| Box::pin(async move { Ok(Transit::Lift(score)) });
+///
+///     // Reusable under any parent's `Composite<Data>` - it never names `AppComposite`/`AltComposite`.
+///     async fn ping<'s, Data>(_comp: &'s mut Composite<Data>, score: Score, lift: ParentLift)
+///         -> Result<Transit<'s, Composite<Data>, ParentLift, AppError>, AppError>
+///     {
+///         Ok(Transit::Lift(lift))
+///     }
+///
+///     #[test]
+///     fn test_reusable_lift() {
+///         let into_app: ParentLift = (APP_DONE, 7).lift_into();
+///         let into_alt: ParentLift = (ALT_DONE, 9).lift_into();
+///
+///         // App's own `init` only knows how to build its own alternative, passing the rest through.
+///         match Uninject::<BuilderPair<AppComposite, Score, Score, AppError>, _>::uninject(into_app) {
+///             Ok((_builder, arg)) => assert_eq!(7, arg),
+///             Err(_remainder) => panic!("expected the App alternative"),
+///         }
+///
+///         let remainder = match Uninject::<BuilderPair<AppComposite, Score, Score, AppError>, _>::uninject(into_alt) {
+///             Ok(_) => panic!("App shouldn't recognize Alt's alternative"),
+///             Err(remainder) => remainder,
+///         };
+///         let result: Result<BuilderPair<AltComposite, Score, Score, AppError>, CNil> = remainder.uninject();
+///         match result {
+///             Ok((_builder, arg)) => assert_eq!(9, arg),
+///             Err(never) => match never {},
+///         }
+///     }
+/// ```
+pub trait LiftInto<Super, Index> {
+    /// Inject `self` as the alternative `Index` names within `Super`
+    fn lift_into(self) -> Super;
+}
+
+impl<Head, Tail> LiftInto<Coproduct<Head, Tail>, Here> for Head {
+    fn lift_into(self) -> Coproduct<Head, Tail> {
+        Coproduct::Inl(self)
+    }
+}
+
+impl<Head, Tail, T, Index> LiftInto<Coproduct<Head, Tail>, There<Index>> for T
+    where T: LiftInto<Tail, Index>
+{
+    fn lift_into(self) -> Coproduct<Head, Tail> {
+        Coproduct::Inr(self.lift_into())
+    }
+}
+
+/// Try to recover the alternative `T` out of a coproduct, the dual of `LiftInto` - what a parent's
+/// `init` uses to pluck the `BuilderPair` it knows how to build, passing every other alternative
+/// through as `Remainder` so an enclosing parent further up can keep trying.
+pub trait Uninject<T, Index>: Sized {
+    /// Every other alternative, still a coproduct, if `T` wasn't the one present
+    type Remainder;
+    /// `Ok(T)` if this coproduct held the alternative `Index` names, `Err(Remainder)` otherwise
+    fn uninject(self) -> Result<T, Self::Remainder>;
+}
+
+impl<Head, Tail> Uninject<Head, Here> for Coproduct<Head, Tail> {
+    type Remainder = Tail;
+
+    fn uninject(self) -> Result<Head, Tail> {
+        match self {
+            Coproduct::Inl(head) => Ok(head),
+            Coproduct::Inr(tail) => Err(tail),
+        }
+    }
+}
+
+impl<Head, Tail, T, Index> Uninject<T, There<Index>> for Coproduct<Head, Tail>
+    where Tail: Uninject<T, Index>
+{
+    type Remainder = Coproduct<Head, <Tail as Uninject<T, Index>>::Remainder>;
+
+    fn uninject(self) -> Result<T, Self::Remainder> {
+        match self {
+            Coproduct::Inl(head) => Err(Coproduct::Inl(head)),
+            Coproduct::Inr(tail) => tail.uninject().map_err(Coproduct::Inr),
+        }
+    }
+}
+
+/// Build a nested `Coproduct<..>` type from a list of alternatives without writing the nesting out
+/// by hand, e.g. `coproduct_type!(A, B, C)` expands to `Coproduct<A, Coproduct<B, Coproduct<C, CNil>>>`.
+/// Injection and uninjection come for free at any depth via `LiftInto`/`Uninject`'s recursive impls -
+/// this macro only saves typing the nesting, it's not what makes additional alternatives possible.
+///
+/// #Examples
+/// ```
+///     use async_hsm::{coproduct_type, Coproduct, CNil};
+///
+///     type Alternatives = coproduct_type!(u32, bool, &'static str);
+///     let _same_type: Alternatives = Coproduct::Inl(1u32);
+/// ```
+#[macro_export]
+macro_rules! coproduct_type {
+    () => { $crate::CNil };
+    ($head:ty $(, $tail:ty)* $(,)?) => {
+        $crate::Coproduct<$head, $crate::coproduct_type!($($tail),*)>
+    };
+}
+
+/// Whether an observed transition stayed within the Composite or lifted out of it
+///
+/// The `Lift` variant carries the debug representation of the `Out` value, since the observer is
+/// shared across Composites instantiated with different `Out` types and can't be generic over all
+/// of them at once.
+#[derive(Debug, Clone)]
+pub enum TransitionKind {
+    /// Transitioning to another state within the same Composite
+    To,
+    /// Lifting out of the Composite, carrying the `{:?}` of the `Out` value
+    Lift(String),
+}
+
+/// A single transition reported to a Composite's observer, fired automatically by `Composite::init`
+/// (and `init_parallel`/`init_with_history`) around every `Transit::To`/`Transit::Lift` it drives.
+///
+/// There is no `state` field: once `init` holds an already-built, type-erased `Transit::To(Handle)`,
+/// it has no way to recover the name of the state function that produced it, only that a transition
+/// happened, at which depth, and - on `Lift` - what came out. Events are only ever produced by
+/// those loops; a state function has no way to report one of its own.
+#[derive(Debug, Clone)]
+pub struct TransitionEvent {
+    /// Nesting depth of the Composite the event occurred in, 0 for the outermost Composite
+    pub depth: u32,
+    pub kind: TransitionKind,
+}
+
+/// Shared handle to an installed observer, clone this into child Composites to propagate tracing
+///
+/// See `Composite::child_context`/`Composite::set_child_context`.
+pub type Observer = Rc<RefCell<Box<dyn FnMut(TransitionEvent)>>>;
+
+/// Shared handle to an installed `on_entry`/`on_exit` callback, see `Composite::set_on_entry`/
+/// `Composite::set_on_exit`. `Rc<RefCell<..>>`-based for the same reason `Observer` is: it needs
+/// to be cloned out of `&mut Composite` before `Composite::init` hands the exclusive borrow to the
+/// first state, so the loop can still reach it afterwards to fire it around every `Transit::To` hop.
+pub type Hook = Rc<RefCell<Box<dyn FnMut()>>>;
+
+/// Caller-chosen name distinguishing one reentrant sub-machine's resume point from another's
+/// sharing the same `HistoryStore`, e.g. a `const PLAY_HISTORY: HistoryKey = "play";` next to the
+/// `Builder` statics for that sub-machine - see `Composite::remember`/`Composite::init_with_history`.
+pub type HistoryKey = &'static str;
+
+/// Shared, type-erased store of resume points for the UML history pseudo-state, keyed by
+/// `HistoryKey` - see `Composite::remember`. Keyed rather than a single slot because
+/// `Composite::child_context` clones the whole store into every descendant, and sibling reentrant
+/// sub-machines under the same parent are literally the same Rust type `Composite<Data>`; without
+/// a key, `Composite::init_with_history` for one sub-machine has no way to tell its own resume
+/// point apart from a sibling's and can silently consume and replay the wrong one. Entries are
+/// erased via `Box<dyn Any>` rather than `BuilderPair<Self, FactoryArg, Out, Err>` directly, since
+/// `Composite<Data>` is generic over `Data` alone and can't also carry `FactoryArg`/`Out`/`Err` as
+/// its own parameters without forcing every nesting level in the crate to name them; `remember`/
+/// `init_with_history` downcast back to the concrete `BuilderPair` at the point of use.
+type HistorySlot = Rc<RefCell<HashMap<HistoryKey, Box<dyn Any>>>>;
+
 /// The structure may be used to share data between states within the same Composite
 pub struct Composite<Data> {
     pub data: Data,
+    on_entry: Option<Hook>,
+    on_exit: Option<Hook>,
+    observer: Option<Observer>,
+    history: HistorySlot,
+    depth: u32,
+}
+
+/// Bundle of cross-cutting `on_entry`/`on_exit` hooks, observer, history slot, and nesting depth a
+/// parent Composite hands down to a freshly constructed child, see `Composite::child_context`/
+/// `Composite::set_child_context`.
+#[derive(Clone)]
+pub struct ChildContext {
+    on_entry: Option<Hook>,
+    on_exit: Option<Hook>,
+    observer: Option<Observer>,
+    history: HistorySlot,
+    depth: u32,
 }
 
 /// Implementing Composite methods
 impl<Data> Composite<Data> {
     /// Create a new Composite instance, sharing the data between all states within the Composite
     pub fn new(data: Data) -> Self {
-        Composite { data: data }
+        Composite { data: data, on_entry: None, on_exit: None, observer: None, history: Rc::new(RefCell::new(HashMap::new())), depth: 0 }
+    }
+
+    /// Register a hook to run automatically whenever `Composite::init` (or `init_parallel`/
+    /// `init_with_history`) enters a state - see `Composite::set_on_exit` for why this can be
+    /// automatic without any state function calling anything itself.
+    pub fn set_on_entry<F: FnMut() + 'static>(&mut self, f: F) {
+        self.on_entry = Some(Rc::new(RefCell::new(Box::new(f))));
+    }
+
+    /// Register a hook to run automatically whenever `Composite::init` (or `init_parallel`/
+    /// `init_with_history`) exits a state.
+    ///
+    /// Every state in the chain of `Transit::To` calls shares one continuous `&mut Composite`
+    /// borrow, from the moment `init` hands it to the first state until the state that finally
+    /// yields `Transit::Lift`; control never returns to `init`'s own stack frame in between, so
+    /// `init` can't call back into `self` at each boundary. Instead it clones the `Hook` handles
+    /// out of `self` before that borrow is handed away, and fires the clones around every
+    /// `Transit::To`/`Transit::Lift` it sees - no state function needs to call anything.
+    ///
+    /// #Examples
+    /// ```
+    ///     use async_std::prelude::*;
+    ///     use async_std::stream;
+    ///     use async_std::task;
+    ///     use async_hsm::{Composite, Transit, Builder, BuilderPair};
+    ///     use std::rc::Rc;
+    ///     use std::cell::RefCell;
+    ///
+    ///     type Score = u32;
+    ///     type AppComposite = Composite<AppData>;
+    ///     type PlayComposite = Composite<AppData>;
+    ///     type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+    ///     type PlayTransit<'s> = Transit<'s, PlayComposite, BuilderPair<AppComposite, Score, Score, AppError>, AppError>;
+    ///     type AppBuilder = Builder<AppComposite, Score, Score, AppError>;
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum AppError { Failure }
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum IoEvent { Terminate }
+    ///
+    ///     #[derive(Debug, Clone)]
+    ///     struct AppData {
+    ///         event: Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>>,
+    ///         entries: Rc<RefCell<u32>>,
+    ///         exits: Rc<RefCell<u32>>,
+    ///     }
+    ///
+    ///     static TERMINATE: AppBuilder = || |comp, score| Box::pin(terminate(comp, score));
+    ///
+    ///     async fn terminate<'s>(_comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         Ok(Transit::Lift(score))
+    ///     }
+    ///
+    ///     async fn ping<'s>(comp: &'s mut PlayComposite, score: Score) -> Result<PlayTransit<'s>, AppError> {
+    ///         let event = comp.data.event.clone();
+    ///         (*event).borrow_mut().next().await;
+    ///         Ok(Transit::Lift((TERMINATE, score)))
+    ///     }
+    ///
+    ///     async fn play<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         let mut play = PlayComposite::new(comp.data.clone());
+    ///         let entries = comp.data.entries.clone();
+    ///         let exits = comp.data.exits.clone();
+    ///         play.set_on_entry(move || *entries.borrow_mut() += 1);
+    ///         play.set_on_exit(move || *exits.borrow_mut() += 1);
+    ///         let (builder, build_arg) = play.init(ping, score).await?;
+    ///         builder()(comp, build_arg).await
+    ///     }
+    ///
+    ///     #[test]
+    ///     fn test_entry_exit() {
+    ///         let event = Rc::new(RefCell::new(stream::from_iter(vec![IoEvent::Terminate])));
+    ///         let entries = Rc::new(RefCell::new(0));
+    ///         let exits = Rc::new(RefCell::new(0));
+    ///         let mut app = AppComposite::new(AppData { event: event, entries: entries.clone(), exits: exits.clone() });
+    ///         let app_entries = entries.clone();
+    ///         let app_exits = exits.clone();
+    ///         app.set_on_entry(move || *app_entries.borrow_mut() += 1);
+    ///         app.set_on_exit(move || *app_exits.borrow_mut() += 1);
+    ///         let result: Result<Score, AppError> = task::block_on(app.init(play, 0));
+    ///         assert_eq!(Ok(0), result);
+    ///         assert_eq!(*entries.borrow(), *exits.borrow());
+    ///     }
+    /// ```
+    pub fn set_on_exit<F: FnMut() + 'static>(&mut self, f: F) {
+        self.on_exit = Some(Rc::new(RefCell::new(Box::new(f))));
+    }
+
+    /// Fire a cloned-out `Hook`, if any. Free function taking the clone by reference rather than a
+    /// method, since by the time `init`'s loop needs to call this, the `&mut self` it cloned the
+    /// hook out of is already on loan to the state chain.
+    fn fire(hook: &Option<Hook>) {
+        if let Some(h) = hook {
+            (h.borrow_mut())();
+        }
+    }
+
+    /// Install an observer to be notified of every transition `Composite::init` (and
+    /// `init_parallel`/`init_with_history`) drives, fired automatically at their single choke
+    /// point the same way `on_entry`/`on_exit` are. Zero overhead when left unset.
+    ///
+    /// #Examples
+    /// ```
+    ///     use async_std::prelude::*;
+    ///     use async_std::stream;
+    ///     use async_std::task;
+    ///     use async_hsm::{Composite, Transit};
+    ///     use std::rc::Rc;
+    ///     use std::cell::RefCell;
+    ///
+    ///     type Score = u32;
+    ///     type AppComposite = Composite<AppData>;
+    ///     type PlayComposite = Composite<AppData>;
+    ///     type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+    ///     type PlayTransit<'s> = Transit<'s, PlayComposite, Score, AppError>;
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum AppError { Failure }
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum IoEvent { Terminate }
+    ///
+    ///     #[derive(Debug, Clone)]
+    ///     struct AppData { event: Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>> }
+    ///
+    ///     async fn ping<'s>(comp: &'s mut PlayComposite, score: Score) -> Result<PlayTransit<'s>, AppError> {
+    ///         let event = comp.data.event.clone();
+    ///         (*event).borrow_mut().next().await;
+    ///         Ok(Transit::Lift(score + 1))
+    ///     }
+    ///
+    ///     async fn play<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         let mut play = PlayComposite::new(comp.data.clone());
+    ///         play.set_child_context(comp.child_context());
+    ///         let score = play.init(ping, score).await?;
+    ///         Ok(Transit::Lift(score))
+    ///     }
+    ///
+    ///     #[test]
+    ///     fn test_tracing() {
+    ///         let event = Rc::new(RefCell::new(stream::from_iter(vec![IoEvent::Terminate])));
+    ///         let trace = Rc::new(RefCell::new(Vec::new()));
+    ///         let trace_sink = trace.clone();
+    ///         let mut app = AppComposite::new(AppData { event: event });
+    ///         app.set_observer(Box::new(move |event| trace_sink.borrow_mut().push((event.depth, format!("{:?}", event.kind)))));
+    ///         let result: Result<Score, AppError> = task::block_on(app.init(play, 0));
+    ///         assert_eq!(Ok(1), result);
+    ///         assert_eq!(*trace.borrow(), vec![(1, "Lift(\"1\")".to_string()), (0, "Lift(\"1\")".to_string())]);
+    ///     }
+    /// ```
+    pub fn set_observer(&mut self, observer: Box<dyn FnMut(TransitionEvent)>) {
+        self.observer = Some(Rc::new(RefCell::new(observer)));
+    }
+
+    /// Clone this Composite's `on_entry`/`on_exit` hooks, observer, history slot, and nesting depth
+    /// into a `ChildContext`, ready to install on a freshly constructed child Composite via
+    /// `Composite::set_child_context` - the same sharing a state function already does for
+    /// `Rc<RefCell<..>>` fields of `data` when handing them to a sub-composite, just applied to
+    /// the cross-cutting hook/tracing/history state instead. Cloning the history slot in this way
+    /// is what lets a freshly constructed child - e.g. a new `PlayComposite` built each time `play`
+    /// runs - still resume from a `Composite::remember` call a previous instance made.
+    pub fn child_context(&self) -> ChildContext {
+        ChildContext {
+            on_entry: self.on_entry.clone(),
+            on_exit: self.on_exit.clone(),
+            observer: self.observer.clone(),
+            history: self.history.clone(),
+            depth: self.depth + 1,
+        }
+    }
+
+    /// Adopt hooks, observer, history slot, and nesting depth obtained from the parent's
+    /// `Composite::child_context`
+    pub fn set_child_context(&mut self, ctx: ChildContext) {
+        self.on_entry = ctx.on_entry;
+        self.on_exit = ctx.on_exit;
+        self.observer = ctx.observer;
+        self.history = ctx.history;
+        self.depth = ctx.depth;
+    }
+
+    /// Report a transition to the installed observer, if any. Called automatically from `init`'s
+    /// (and `init_parallel`'s/`init_with_history`'s) own loop, see `Composite::set_observer`.
+    fn notify(observer: &Option<Observer>, depth: u32, kind: TransitionKind) {
+        if let Some(observer) = observer {
+            (observer.borrow_mut())(TransitionEvent { depth, kind });
+        }
     }
 
     /// Composition of states, only one sub-state at a time. The function f is initializing the  first sub state.
@@ -246,15 +673,392 @@ impl<Data> Composite<Data> {
 
         where Factory: FnOnce(&'s mut Self, FactoryArg) -> Fut,
               Fut: Future<Output=Result<Transit<'s, Self, Out, Err>, Err>>,
-            Out: Sized + Copy
+            Out: Sized + Copy + std::fmt::Debug
+    {
+        let on_entry = self.on_entry.clone();
+        let on_exit = self.on_exit.clone();
+        let observer = self.observer.clone();
+        let depth = self.depth;
+
+        Self::fire(&on_entry);
+        let mut trans = match f(self, arg).await {
+            Ok(trans) => trans,
+            Err(err) => { Self::fire(&on_exit); return Err(err); }
+        };
+
+        loop {
+            trans = match trans {
+                Transit::To(h) => {
+                    Self::notify(&observer, depth, TransitionKind::To);
+                    Self::fire(&on_exit);
+                    Self::fire(&on_entry);
+                    match h.await {
+                        Ok(trans) => trans,
+                        Err(err) => { Self::fire(&on_exit); return Err(err); }
+                    }
+                }
+                Transit::Lift(lift) => {
+                    if observer.is_some() {
+                        Self::notify(&observer, depth, TransitionKind::Lift(format!("{:?}", lift)));
+                    }
+                    Self::fire(&on_exit);
+                    return Ok(lift)
+                }
+            }
+        }
+    }
+
+    /// Composition of orthogonal (UML-style parallel) regions, each region driving its own
+    /// `Transit::To`/`Transit::Lift` loop concurrently, sharing this Composite's `data`.
+    ///
+    /// Each region is handed its own freshly cloned `Composite<Data>`, so no region ever holds
+    /// a `&mut Composite` overlapping another region's borrow across an `.await` point. `Data`
+    /// itself, though, is shared by handle into every region the same way a sub-composite shares
+    /// it with `play()`'s pattern, so any `Rc<RefCell<..>>` field inside it is the *same* cell in
+    /// every region - fine for state a region only ever touches without holding it borrowed across
+    /// an `.await` (a shared counter, an accumulator), but NOT for a single `Stream` (or any other
+    /// single-consumer source) that more than one region polls: `StreamExt::next` holds its `&mut`
+    /// receiver borrowed for the whole `.await`, and regions genuinely interleave at `.await`
+    /// points once they actually suspend, so two regions polling the same shared stream at once
+    /// panic with "already borrowed". Give each region its own independent stream/source instead of
+    /// fanning one out to many regions - e.g. key `Data` by region, as the example below does.
+    ///
+    /// The regions are polled together via `futures::future::join_all`, so they interleave at
+    /// `.await` points rather than truly running in parallel. `init_parallel` resolves once every
+    /// region has produced a `Transit::Lift`, yielding their `Out` values in region order; if any
+    /// region returns an `Err`, the first one in region order is reported once all regions have
+    /// finished.
+    ///
+    /// All regions share one `FactoryArg`/`Out`/`Err` triple, since they are driven from a single
+    /// `Vec<(Builder<Self, FactoryArg, Out, Err>, FactoryArg)>` - unlike a true ECS-style join over
+    /// heterogeneous components, a region that needs its own distinct types has to be wrapped in a
+    /// shared `Out` enum (or boxed/erased some other way) by the caller, there is no built-in
+    /// per-region typing here.
+    ///
+    /// Each region's `Composite` adopts this Composite's `on_entry`/`on_exit` hooks and observer via
+    /// `Composite::child_context`, the same as any other sub-composite built with `play`'s pattern.
+    ///
+    /// #Examples
+    /// ```
+    ///     use async_std::prelude::*;
+    ///     use async_std::stream;
+    ///     use async_std::task;
+    ///     use async_hsm::{Composite, Transit, Builder};
+    ///     use std::rc::Rc;
+    ///     use std::cell::RefCell;
+    ///     use std::time::Duration;
+    ///
+    ///     type Lit = bool;
+    ///     type RegionId = usize;
+    ///     type RegionArg = (RegionId, Lit);
+    ///     type AppComposite = Composite<AppData>;
+    ///     type AppTransit<'s> = Transit<'s, AppComposite, Lit, AppError>;
+    ///     type AppBuilder = Builder<AppComposite, RegionArg, Lit, AppError>;
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum AppError { Failure }
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum IoEvent { Toggle, Terminate }
+    ///
+    ///     // One independent stream per region, not one stream shared by every region - see above
+    ///     // for why fanning a single stream out to concurrently-polled regions panics.
+    ///     #[derive(Debug, Clone)]
+    ///     struct AppData { events: Vec<Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>>> }
+    ///
+    ///     async fn region<'s>(comp: &'s mut AppComposite, (id, lit): RegionArg) -> Result<AppTransit<'s>, AppError> {
+    ///         // Genuinely suspends here, so both regions are actually in flight together rather
+    ///         // than one draining its whole stream before the other is ever polled.
+    ///         task::sleep(Duration::from_millis(1 + id as u64)).await;
+    ///         let event = comp.data.events[id].clone();
+    ///         let next = (*event).borrow_mut().next().await;
+    ///         match next {
+    ///             Some(IoEvent::Toggle) => Ok(Transit::To(Box::pin(region(comp, (id, !lit))))),
+    ///             _ => Ok(Transit::Lift(lit)),
+    ///         }
+    ///     }
+    ///
+    ///     static REGION: AppBuilder = || |comp, arg| Box::pin(region(comp, arg));
+    ///
+    ///     #[test]
+    ///     fn test_orthogonal_regions() {
+    ///         let sequence = vec![IoEvent::Toggle, IoEvent::Terminate];
+    ///         let events = vec![
+    ///             Rc::new(RefCell::new(stream::from_iter(sequence.clone()))),
+    ///             Rc::new(RefCell::new(stream::from_iter(sequence))),
+    ///         ];
+    ///         let mut app = AppComposite::new(AppData { events: events });
+    ///         let regions = vec![(REGION, (0, false)), (REGION, (1, true))];
+    ///         let result: Result<Vec<Lit>, AppError> = task::block_on(app.init_parallel(regions));
+    ///         assert_eq!(Ok(vec![true, false]), result);
+    ///     }
+    /// ```
+    pub async fn init_parallel<FactoryArg, Out, Err>(
+        &mut self,
+        regions: Vec<(Builder<Self, FactoryArg, Out, Err>, FactoryArg)>,
+    ) -> Result<Vec<Out>, Err>
+        where Data: Clone,
+              Out: Sized + Copy + std::fmt::Debug
+    {
+        let child_context = self.child_context();
+        let tasks = regions.into_iter().map(|(builder, arg)| {
+            let data = self.data.clone();
+            let child_context = child_context.clone();
+            async move {
+                let mut region = Composite::new(data);
+                region.set_child_context(child_context);
+                region.init(builder(), arg).await
+            }
+        });
+
+        futures::future::join_all(tasks).await.into_iter().collect()
+    }
+
+    /// Record the `BuilderPair` to resume into on the next `Composite::init_with_history` call made
+    /// with the same `key`, into this very Composite's own history store - the UML history
+    /// pseudo-state. A state function calls this itself, right before it lifts, naming the state it
+    /// wants re-entered directly next time instead of replaying from the initial factory.
+    ///
+    /// The store lives on the Composite, not on `Data`: `Composite::child_context` clones it into
+    /// every freshly constructed child the same way it clones `on_entry`/`on_exit`/the observer, so
+    /// a new `PlayComposite` built on the next `play` call still sees what the previous one
+    /// remembered. Because that store is shared by the whole `child_context` chain, and sibling
+    /// reentrant sub-machines under the same parent are literally the same Rust type
+    /// `Composite<Data>`, `key` is what keeps one sub-machine's resume point from colliding with
+    /// another's - give each reentrant sub-machine its own `HistoryKey` constant, the same way it
+    /// already gets its own `Builder` statics.
+    ///
+    /// There's no way to record automatically from `init`'s own loop the way `on_entry`/`on_exit`/
+    /// the observer now fire automatically: by the time `init` sees a `Transit::To(Handle)`, the
+    /// `Handle` is already an opaque, type-erased future and no longer reveals the `Builder`/arg
+    /// pair that produced it, so a state still has to call `remember` itself to name that pair
+    /// while it still can.
+    ///
+    /// Whether this amounts to shallow or deep history is a question of which state functions call
+    /// `remember`, not a runtime flag here: have only the immediate sub-state remember for shallow
+    /// history, or have every nested Composite remember its own resume point the same way for deep
+    /// history, so restoring the outer slot cascades into each inner level's own resume point.
+    pub fn remember<FactoryArg, Out, Err>(&self, key: HistoryKey, resume: BuilderPair<Self, FactoryArg, Out, Err>)
+        where Data: 'static,
+              FactoryArg: 'static,
+              Out: Sized + Copy + 'static,
+              Err: 'static,
+    {
+        self.history.borrow_mut().insert(key, Box::new(resume));
+    }
+
+    /// Like `Composite::init`, but if this Composite's history store holds a `BuilderPair` recorded
+    /// by a prior `Composite::remember` call under this same `key`, enter that instead of the
+    /// supplied initial factory `f`/`arg` - the UML history pseudo-state applied to re-entering a
+    /// Composite. The entry is consumed on use, falling back to `f`/`arg` again on the entry after
+    /// that unless something calls `remember` again. `key` must match the `HistoryKey` the
+    /// corresponding `remember` call used - see `Composite::remember` for why a key is required.
+    ///
+    /// #Examples
+    /// ```
+    ///     use async_std::prelude::*;
+    ///     use async_std::stream;
+    ///     use async_std::task;
+    ///     use async_hsm::{Composite, Transit, Builder, BuilderPair, HistoryKey};
+    ///     use std::rc::Rc;
+    ///     use std::cell::RefCell;
+    ///
+    ///     type Score = u32;
+    ///     type AppComposite = Composite<AppData>;
+    ///     type PlayComposite = Composite<AppData>;
+    ///     type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+    ///     type PlayTransit<'s> = Transit<'s, PlayComposite, BuilderPair<AppComposite, Score, Score, AppError>, AppError>;
+    ///     type AppBuilder = Builder<AppComposite, Score, Score, AppError>;
+    ///     type PlayBuilder = Builder<PlayComposite, Score, BuilderPair<AppComposite, Score, Score, AppError>, AppError>;
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum AppError { Failure }
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum IoEvent { Play, Next, Menu, Terminate }
+    ///
+    ///     #[derive(Debug, Clone)]
+    ///     struct AppData { event: Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>> }
+    ///
+    ///     static TO_MENU: AppBuilder = || |comp, score| Box::pin(menu(comp, score));
+    ///     static RESUME_PONG: PlayBuilder = || |comp, score| Box::pin(pong(comp, score));
+    ///     const PLAY_HISTORY: HistoryKey = "play";
+    ///
+    ///     async fn ping<'s>(comp: &'s mut PlayComposite, score: Score) -> Result<PlayTransit<'s>, AppError> {
+    ///         let event = comp.data.event.clone();
+    ///         let next = (*event).borrow_mut().next().await;
+    ///         match next {
+    ///             Some(IoEvent::Next) => Ok(Transit::To(Box::pin(pong(comp, score + 1)))),
+    ///             _ => Ok(Transit::Lift((TO_MENU, score))),
+    ///         }
+    ///     }
+    ///
+    ///     async fn pong<'s>(comp: &'s mut PlayComposite, score: Score) -> Result<PlayTransit<'s>, AppError> {
+    ///         let event = comp.data.event.clone();
+    ///         let next = (*event).borrow_mut().next().await;
+    ///         match next {
+    ///             Some(IoEvent::Menu) => {
+    ///                 comp.remember(PLAY_HISTORY, (RESUME_PONG, score));
+    ///                 Ok(Transit::Lift((TO_MENU, score)))
+    ///             }
+    ///             _ => Ok(Transit::Lift((TO_MENU, score))),
+    ///         }
+    ///     }
+    ///
+    ///     async fn play<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         let mut play = PlayComposite::new(comp.data.clone());
+    ///         play.set_child_context(comp.child_context());
+    ///         let (builder, build_arg) = play.init_with_history(PLAY_HISTORY, ping, score).await?;
+    ///         builder()(comp, build_arg).await
+    ///     }
+    ///
+    ///     async fn menu<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         let event = comp.data.event.clone();
+    ///         let next = (*event).borrow_mut().next().await;
+    ///         match next {
+    ///             Some(IoEvent::Play) => Ok(Transit::To(Box::pin(play(comp, score)))),
+    ///             _ => Ok(Transit::Lift(score)),
+    ///         }
+    ///     }
+    ///
+    ///     #[test]
+    ///     fn test_history() {
+    ///         // Enter Play, advance ping -> pong, then leave a remembered resume point in pong
+    ///         // before going back to Menu and re-entering Play a second time: it resumes directly
+    ///         // in pong instead of replaying ping, then runs to completion once the stream is dry.
+    ///         let sequence = vec![IoEvent::Play, IoEvent::Next, IoEvent::Menu, IoEvent::Play, IoEvent::Terminate];
+    ///         let event = Rc::new(RefCell::new(stream::from_iter(sequence)));
+    ///         let mut app = AppComposite::new(AppData { event: event });
+    ///         let result: Result<Score, AppError> = task::block_on(app.init(menu, 0));
+    ///         assert_eq!(Ok(1), result);
+    ///     }
+    /// ```
+    pub async fn init_with_history<'s, Factory, FactoryArg, Out, Err, Fut>(
+        &'s mut self,
+        key: HistoryKey,
+        f: Factory,
+        arg: FactoryArg,
+    ) -> Result<Out, Err>
+        where Factory: FnOnce(&'s mut Self, FactoryArg) -> Fut,
+              Fut: Future<Output=Result<Transit<'s, Self, Out, Err>, Err>>,
+              Data: 'static,
+              FactoryArg: 'static,
+              Out: Sized + Copy + std::fmt::Debug + 'static,
+              Err: 'static,
     {
-        let mut trans = f(self, arg).await?;
+        let on_entry = self.on_entry.clone();
+        let on_exit = self.on_exit.clone();
+        let observer = self.observer.clone();
+        let depth = self.depth;
+        let history = self.history.clone();
+
+        let resume = history.borrow_mut().remove(key)
+            .and_then(|boxed| boxed.downcast::<BuilderPair<Self, FactoryArg, Out, Err>>().ok())
+            .map(|boxed| *boxed);
+        Self::fire(&on_entry);
+        let resumed = match resume {
+            Some((builder, resume_arg)) => builder()(self, resume_arg).await,
+            None => f(self, arg).await,
+        };
+        let mut trans = match resumed {
+            Ok(trans) => trans,
+            Err(err) => { Self::fire(&on_exit); return Err(err); }
+        };
 
         loop {
             trans = match trans {
-                Transit::To(h) => h.await?,
-                Transit::Lift(lift) => return Ok(lift)
+                Transit::To(h) => {
+                    Self::notify(&observer, depth, TransitionKind::To);
+                    Self::fire(&on_exit);
+                    Self::fire(&on_entry);
+                    match h.await {
+                        Ok(trans) => trans,
+                        Err(err) => { Self::fire(&on_exit); return Err(err); }
+                    }
+                }
+                Transit::Lift(lift) => {
+                    if observer.is_some() {
+                        Self::notify(&observer, depth, TransitionKind::Lift(format!("{:?}", lift)));
+                    }
+                    Self::fire(&on_exit);
+                    return Ok(lift)
+                }
             }
         }
     }
+
+    /// Race a state's "normal" future against a timeout, lifting `on_timeout` if the former has
+    /// not resolved within `duration`.
+    ///
+    /// This is the crate's `after`/timeout primitive for time-triggered transitions: wrap a
+    /// Handle the way it would otherwise be wrapped in `Transit::To`, and the result is itself a
+    /// Handle that can be used exactly the same way. If no relevant event arrives in time, `fut`
+    /// is dropped - releasing any borrow of `comp.data` it held - and the race resolves to
+    /// `Transit::Lift(on_timeout)`, the same Lift-style escalation every other state uses to hand
+    /// control back to the enclosing Composite; `Out` already has to be `Copy` for `Transit::Lift`,
+    /// so a timeout value fits that bound for free.
+    ///
+    /// #Examples
+    /// ```
+    ///     use async_std::prelude::*;
+    ///     use async_std::task;
+    ///     use async_hsm::{Composite, Transit, Builder, BuilderPair};
+    ///     use futures::stream;
+    ///     use std::rc::Rc;
+    ///     use std::cell::RefCell;
+    ///     use std::time::Duration;
+    ///
+    ///     type Score = u32;
+    ///     type AppComposite = Composite<AppData>;
+    ///     type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum AppError { Failure }
+    ///
+    ///     #[derive(Debug, Clone, PartialEq)]
+    ///     enum IoEvent { Ping }
+    ///
+    ///     #[derive(Debug, Clone)]
+    ///     struct AppData { event: Rc<RefCell<stream::Pending<IoEvent>>> }
+    ///
+    ///     // Never yields an event, so `idle` only ever advances via the timeout below.
+    ///     async fn idle<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         let event = comp.data.event.clone();
+    ///         let next = (*event).borrow_mut().next().await;
+    ///         match next {
+    ///             Some(IoEvent::Ping) => Ok(Transit::To(Box::pin(idle(comp, score + 1)))),
+    ///             None => Ok(Transit::Lift(score)),
+    ///         }
+    ///     }
+    ///
+    ///     async fn watchdog<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+    ///         let duration = Duration::from_millis(20);
+    ///         Ok(Transit::To(AppComposite::race_timeout(Box::pin(idle(comp, score)), duration, 999)))
+    ///     }
+    ///
+    ///     #[test]
+    ///     fn test_timeout() {
+    ///         let event = Rc::new(RefCell::new(stream::pending::<IoEvent>()));
+    ///         let start_score = 0;
+    ///         let mut app = AppComposite::new(AppData { event: event });
+    ///         let result: Result<Score, AppError> = task::block_on(app.init(watchdog, start_score));
+    ///         assert_eq!(Ok(999), result);
+    ///     }
+    /// ```
+    pub fn race_timeout<'s, Out, Err>(
+        fut: Handle<'s, Self, Out, Err>,
+        duration: Duration,
+        on_timeout: Out,
+    ) -> Handle<'s, Self, Out, Err>
+        where Out: Sized + Copy + 's,
+              Err: 's,
+              Data: 's
+    {
+        Box::pin(async move {
+            match async_std::future::timeout(duration, fut).await {
+                Ok(transit) => transit,
+                Err(_) => Ok(Transit::Lift(on_timeout)),
+            }
+        })
+    }
 }
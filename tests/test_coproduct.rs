@@ -0,0 +1,94 @@
+#![allow(dead_code)]
+
+pub use async_hsm;
+
+#[cfg(test)]
+mod test_coproduct {
+    use async_hsm::{Composite, Transit, Builder, BuilderPair, CNil, LiftInto, Uninject, coproduct_type};
+
+    type Score = u32;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AppError { Failure }
+
+    struct AppData;
+    struct AltData;
+
+    type AppComposite = Composite<AppData>;
+    type AltComposite = Composite<AltData>;
+    type AppBuilder = Builder<AppComposite, Score, Score, AppError>;
+    type AltBuilder = Builder<AltComposite, Score, Score, AppError>;
+
+    // Every parent `ping` can lift into - add a third parent here without touching `ping` itself.
+    type ParentLift = coproduct_type!(
+        BuilderPair<AppComposite, Score, Score, AppError>,
+        BuilderPair<AltComposite, Score, Score, AppError>
+    );
+
+    static APP_DONE: AppBuilder = || |_comp, score| Box::pin(async move { Ok(Transit::Lift(score)) });
+    static ALT_DONE: AltBuilder = || |_comp, score| Box::pin(async move { Ok(Transit::Lift(score)) });
+
+    // Reusable under any parent's `Composite<Data>` - it never names `AppComposite`/`AltComposite`.
+    async fn ping<'s, Data>(_comp: &'s mut Composite<Data>, _score: Score, lift: ParentLift)
+        -> Result<Transit<'s, Composite<Data>, ParentLift, AppError>, AppError>
+    {
+        Ok(Transit::Lift(lift))
+    }
+
+    #[test]
+    fn each_parent_uninjects_only_its_own_alternative() {
+        let into_app: ParentLift = (APP_DONE, 7).lift_into();
+        let into_alt: ParentLift = (ALT_DONE, 9).lift_into();
+
+        // App's own `init` only knows how to build its own alternative, passing the rest through.
+        match Uninject::<BuilderPair<AppComposite, Score, Score, AppError>, _>::uninject(into_app) {
+            Ok((_builder, arg)) => assert_eq!(7, arg),
+            Err(_remainder) => panic!("expected the App alternative"),
+        }
+
+        let remainder = match Uninject::<BuilderPair<AppComposite, Score, Score, AppError>, _>::uninject(into_alt) {
+            Ok(_) => panic!("App shouldn't recognize Alt's alternative"),
+            Err(remainder) => remainder,
+        };
+        let result: Result<BuilderPair<AltComposite, Score, Score, AppError>, CNil> = remainder.uninject();
+        match result {
+            Ok((_builder, arg)) => assert_eq!(9, arg),
+            Err(never) => match never {},
+        }
+    }
+
+    #[test]
+    fn reusable_ping_lifts_into_either_parents_composite() {
+        let mut app = AppComposite::new(AppData);
+        let mut alt = AltComposite::new(AltData);
+
+        let into_app: ParentLift = (APP_DONE, 1).lift_into();
+        let into_alt: ParentLift = (ALT_DONE, 2).lift_into();
+
+        let app_lift = async_std::task::block_on(ping(&mut app, 1, into_app)).unwrap();
+        let alt_lift = async_std::task::block_on(ping(&mut alt, 2, into_alt)).unwrap();
+
+        match app_lift {
+            Transit::Lift(lift) => match Uninject::<BuilderPair<AppComposite, Score, Score, AppError>, _>::uninject(lift) {
+                Ok((_builder, arg)) => assert_eq!(1, arg),
+                Err(_) => panic!("expected the App alternative"),
+            },
+            Transit::To(_) => panic!("ping always lifts"),
+        }
+
+        match alt_lift {
+            Transit::Lift(lift) => {
+                let remainder = match Uninject::<BuilderPair<AppComposite, Score, Score, AppError>, _>::uninject(lift) {
+                    Ok(_) => panic!("App shouldn't recognize Alt's alternative"),
+                    Err(remainder) => remainder,
+                };
+                let result: Result<BuilderPair<AltComposite, Score, Score, AppError>, CNil> = remainder.uninject();
+                match result {
+                    Ok((_builder, arg)) => assert_eq!(2, arg),
+                    Err(never) => match never {},
+                }
+            }
+            Transit::To(_) => panic!("ping always lifts"),
+        }
+    }
+}
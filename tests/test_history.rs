@@ -0,0 +1,97 @@
+#![allow(dead_code)]
+
+pub use async_hsm;
+
+#[cfg(test)]
+mod test_history {
+    use async_std::prelude::*;
+    use async_std::stream;
+    use async_std::task;
+    use async_hsm::{Composite, Transit, Builder, BuilderPair, HistoryKey};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    type Score = u32;
+    type AppComposite = Composite<AppData>;
+    type SubComposite = Composite<AppData>;
+    type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+    type SubTransit<'s> = Transit<'s, SubComposite, BuilderPair<AppComposite, Score, Score, AppError>, AppError>;
+    type AppBuilder = Builder<AppComposite, Score, Score, AppError>;
+    type SubBuilder = Builder<SubComposite, Score, BuilderPair<AppComposite, Score, Score, AppError>, AppError>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AppError { Failure }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum IoEvent { EnterPlay, EnterOpt, Terminate }
+
+    #[derive(Debug, Clone)]
+    struct AppData { event: Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>> }
+
+    const PLAY_HISTORY: HistoryKey = "play";
+    const OPT_HISTORY: HistoryKey = "opt";
+
+    static TO_MENU: AppBuilder = || |comp, score| Box::pin(menu(comp, score));
+    static RESUME_PLAY_INNER: SubBuilder = || |comp, score| Box::pin(play_inner(comp, score + 1000));
+    static RESUME_OPT_INNER: SubBuilder = || |comp, score| Box::pin(opt_inner(comp, score + 2000));
+
+    // Two independent reentrant sub-machines sharing one parent - both are the same Rust type
+    // `Composite<AppData>`, so only their distinct `HistoryKey` tells their resume points apart.
+    async fn play_inner<'s>(comp: &'s mut SubComposite, score: Score) -> Result<SubTransit<'s>, AppError> {
+        comp.remember(PLAY_HISTORY, (RESUME_PLAY_INNER, score));
+        Ok(Transit::Lift((TO_MENU, score)))
+    }
+
+    async fn opt_inner<'s>(comp: &'s mut SubComposite, score: Score) -> Result<SubTransit<'s>, AppError> {
+        comp.remember(OPT_HISTORY, (RESUME_OPT_INNER, score));
+        Ok(Transit::Lift((TO_MENU, score)))
+    }
+
+    async fn play<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        let mut sub = SubComposite::new(comp.data.clone());
+        sub.set_child_context(comp.child_context());
+        let (builder, build_arg) = sub.init_with_history(PLAY_HISTORY, play_inner, score).await?;
+        builder()(comp, build_arg).await
+    }
+
+    async fn opt<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        let mut sub = SubComposite::new(comp.data.clone());
+        sub.set_child_context(comp.child_context());
+        let (builder, build_arg) = sub.init_with_history(OPT_HISTORY, opt_inner, score).await?;
+        builder()(comp, build_arg).await
+    }
+
+    async fn menu<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        let event = comp.data.event.clone();
+        let next = (*event).borrow_mut().next().await;
+        match next {
+            Some(IoEvent::EnterPlay) => Ok(Transit::To(Box::pin(play(comp, score)))),
+            Some(IoEvent::EnterOpt) => Ok(Transit::To(Box::pin(opt(comp, score)))),
+            _ => Ok(Transit::Lift(score)),
+        }
+    }
+
+    #[test]
+    fn distinct_keys_keep_sibling_sub_machines_from_colliding() {
+        // Entering Play remembers a resume point under "play". Entering Opt next must run Opt's
+        // own initial state rather than being silently handed Play's remembered `BuilderPair`.
+        let sequence = vec![IoEvent::EnterPlay, IoEvent::EnterOpt, IoEvent::Terminate];
+        let event = Rc::new(RefCell::new(stream::from_iter(sequence)));
+        let mut app = AppComposite::new(AppData { event: event });
+
+        let result: Result<Score, AppError> = task::block_on(app.init(menu, 0));
+
+        assert_eq!(Ok(0), result, "Opt must run its own initial state, not Play's remembered one");
+    }
+
+    #[test]
+    fn remembered_resume_point_is_honored_on_next_entry_with_same_key() {
+        let sequence = vec![IoEvent::EnterPlay, IoEvent::EnterPlay, IoEvent::Terminate];
+        let event = Rc::new(RefCell::new(stream::from_iter(sequence)));
+        let mut app = AppComposite::new(AppData { event: event });
+
+        let result: Result<Score, AppError> = task::block_on(app.init(menu, 0));
+
+        assert_eq!(Ok(1000), result, "second entry into Play must resume via RESUME_PLAY_INNER");
+    }
+}
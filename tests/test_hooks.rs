@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+pub use async_hsm;
+
+#[cfg(test)]
+mod test_hooks {
+    use async_std::task;
+    use async_hsm::{Composite, Transit};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    type Score = u32;
+    type AppComposite = Composite<AppData>;
+    type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AppError { Failure }
+
+    #[derive(Debug, Clone)]
+    struct AppData {
+        entries: Rc<RefCell<u32>>,
+        exits: Rc<RefCell<u32>>,
+    }
+
+    async fn failing_state<'s>(_comp: &'s mut AppComposite, _score: Score) -> Result<AppTransit<'s>, AppError> {
+        Err(AppError::Failure)
+    }
+
+    async fn to_failing<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        Ok(Transit::To(Box::pin(failing_state(comp, score))))
+    }
+
+    #[test]
+    fn on_exit_fires_even_when_the_initial_state_errors() {
+        let entries = Rc::new(RefCell::new(0));
+        let exits = Rc::new(RefCell::new(0));
+        let mut app = AppComposite::new(AppData { entries: entries.clone(), exits: exits.clone() });
+        let app_entries = entries.clone();
+        let app_exits = exits.clone();
+        app.set_on_entry(move || *app_entries.borrow_mut() += 1);
+        app.set_on_exit(move || *app_exits.borrow_mut() += 1);
+
+        let result: Result<Score, AppError> = task::block_on(app.init(failing_state, 0));
+
+        assert_eq!(Err(AppError::Failure), result);
+        assert_eq!(*entries.borrow(), *exits.borrow(), "entry/exit counts must stay symmetric even on error");
+    }
+
+    #[test]
+    fn on_exit_fires_even_when_a_transit_to_errors() {
+        let entries = Rc::new(RefCell::new(0));
+        let exits = Rc::new(RefCell::new(0));
+        let mut app = AppComposite::new(AppData { entries: entries.clone(), exits: exits.clone() });
+        let app_entries = entries.clone();
+        let app_exits = exits.clone();
+        app.set_on_entry(move || *app_entries.borrow_mut() += 1);
+        app.set_on_exit(move || *app_exits.borrow_mut() += 1);
+
+        let result: Result<Score, AppError> = task::block_on(app.init(to_failing, 0));
+
+        assert_eq!(Err(AppError::Failure), result);
+        assert_eq!(*entries.borrow(), 2, "to_failing and failing_state both entered");
+        assert_eq!(*entries.borrow(), *exits.borrow(), "entry/exit counts must stay symmetric even on error");
+    }
+}
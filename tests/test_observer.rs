@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+pub use async_hsm;
+
+#[cfg(test)]
+mod test_observer {
+    use async_std::prelude::*;
+    use async_std::stream;
+    use async_std::task;
+    use async_hsm::{Composite, Transit};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    type Score = u32;
+    type AppComposite = Composite<AppData>;
+    type PlayComposite = Composite<AppData>;
+    type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+    type PlayTransit<'s> = Transit<'s, PlayComposite, Score, AppError>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AppError { Failure }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum IoEvent { Terminate }
+
+    #[derive(Debug, Clone)]
+    struct AppData { event: Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>> }
+
+    async fn ping<'s>(comp: &'s mut PlayComposite, score: Score) -> Result<PlayTransit<'s>, AppError> {
+        let event = comp.data.event.clone();
+        (*event).borrow_mut().next().await;
+        Ok(Transit::Lift(score + 1))
+    }
+
+    async fn play<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        let mut play = PlayComposite::new(comp.data.clone());
+        play.set_child_context(comp.child_context());
+        let score = play.init(ping, score).await?;
+        Ok(Transit::Lift(score))
+    }
+
+    #[test]
+    fn observer_reports_every_transition_without_states_calling_it() {
+        let event = Rc::new(RefCell::new(stream::from_iter(vec![IoEvent::Terminate])));
+        let trace = Rc::new(RefCell::new(Vec::new()));
+        let trace_sink = trace.clone();
+        let mut app = AppComposite::new(AppData { event: event });
+        app.set_observer(Box::new(move |event| trace_sink.borrow_mut().push((event.depth, format!("{:?}", event.kind)))));
+
+        let result: Result<Score, AppError> = task::block_on(app.init(play, 0));
+
+        assert_eq!(Ok(1), result);
+        assert_eq!(*trace.borrow(), vec![(1, "Lift(\"1\")".to_string()), (0, "Lift(\"1\")".to_string())]);
+    }
+}
@@ -0,0 +1,89 @@
+#![allow(dead_code)]
+
+pub use async_hsm;
+
+#[cfg(test)]
+mod test_parallel {
+    use async_std::prelude::*;
+    use async_std::stream;
+    use async_std::task;
+    use async_hsm::{Composite, Transit, Builder};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    type Lit = bool;
+    type RegionId = usize;
+    type RegionArg = (RegionId, Lit);
+    type AppComposite = Composite<AppData>;
+    type AppTransit<'s> = Transit<'s, AppComposite, Lit, AppError>;
+    type AppBuilder = Builder<AppComposite, RegionArg, Lit, AppError>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AppError { Failure }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum IoEvent { Toggle, Terminate }
+
+    #[derive(Debug, Clone)]
+    struct AppData { events: Vec<Rc<RefCell<stream::FromIter<std::vec::IntoIter<IoEvent>>>>> }
+
+    // Each region polls its own independent stream, identified by `id` - see the doc comment on
+    // `Composite::init_parallel` for why sharing one stream across concurrently-polled regions is
+    // unsound rather than just racy.
+    async fn region<'s>(comp: &'s mut AppComposite, (id, lit): RegionArg) -> Result<AppTransit<'s>, AppError> {
+        // Genuinely suspends here, so both regions are actually in flight together rather than one
+        // draining its whole stream before the other is ever polled.
+        task::sleep(Duration::from_millis(1 + id as u64)).await;
+        let event = comp.data.events[id].clone();
+        let next = (*event).borrow_mut().next().await;
+        match next {
+            Some(IoEvent::Toggle) => Ok(Transit::To(Box::pin(region(comp, (id, !lit))))),
+            _ => Ok(Transit::Lift(lit)),
+        }
+    }
+
+    static REGION: AppBuilder = || |comp, arg| Box::pin(region(comp, arg));
+
+    #[test]
+    fn regions_with_independent_streams_interleave_safely() {
+        let sequence = vec![IoEvent::Toggle, IoEvent::Terminate];
+        let events = vec![
+            Rc::new(RefCell::new(stream::from_iter(sequence.clone()))),
+            Rc::new(RefCell::new(stream::from_iter(sequence))),
+        ];
+        let mut app = AppComposite::new(AppData { events: events });
+        let regions = vec![(REGION, (0, false)), (REGION, (1, true))];
+
+        let result: Result<Vec<Lit>, AppError> = task::block_on(app.init_parallel(regions));
+
+        assert_eq!(Ok(vec![true, false]), result);
+    }
+
+    // Holds the borrow across the `.await`, the way naively sharing one stream across regions
+    // would - demonstrates the hazard the doc comment on `Composite::init_parallel` warns against.
+    async fn shared_region<'s>(comp: &'s mut AppComposite, (id, lit): RegionArg) -> Result<AppTransit<'s>, AppError> {
+        let event = comp.data.events[0].clone();
+        let mut borrow = event.borrow_mut();
+        task::sleep(Duration::from_millis(1)).await;
+        let next = borrow.next().await;
+        drop(borrow);
+        match next {
+            Some(IoEvent::Toggle) => Ok(Transit::To(Box::pin(shared_region(comp, (id, !lit))))),
+            _ => Ok(Transit::Lift(lit)),
+        }
+    }
+
+    static SHARED_REGION: AppBuilder = || |comp, arg| Box::pin(shared_region(comp, arg));
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn sharing_one_stream_across_regions_panics() {
+        let sequence = vec![IoEvent::Toggle, IoEvent::Terminate];
+        let events = vec![Rc::new(RefCell::new(stream::from_iter(sequence)))];
+        let mut app = AppComposite::new(AppData { events: events });
+        let regions = vec![(SHARED_REGION, (0, false)), (SHARED_REGION, (0, true))];
+
+        let _: Result<Vec<Lit>, AppError> = task::block_on(app.init_parallel(regions));
+    }
+}
@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+
+pub use async_hsm;
+
+#[cfg(test)]
+mod test_timeout {
+    use async_std::prelude::*;
+    use async_std::task;
+    use async_hsm::{Composite, Transit};
+    use futures::stream;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    type Score = u32;
+    type AppComposite = Composite<AppData>;
+    type AppTransit<'s> = Transit<'s, AppComposite, Score, AppError>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum AppError { Failure }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum IoEvent { Ping }
+
+    #[derive(Debug, Clone)]
+    struct AppData { event: Rc<RefCell<stream::Pending<IoEvent>>> }
+
+    // Never yields an event, so `idle` only ever advances via the timeout below.
+    async fn idle<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        let event = comp.data.event.clone();
+        let next = (*event).borrow_mut().next().await;
+        match next {
+            Some(IoEvent::Ping) => Ok(Transit::To(Box::pin(idle(comp, score + 1)))),
+            None => Ok(Transit::Lift(score)),
+        }
+    }
+
+    async fn watchdog<'s>(comp: &'s mut AppComposite, score: Score) -> Result<AppTransit<'s>, AppError> {
+        let duration = Duration::from_millis(20);
+        Ok(Transit::To(AppComposite::race_timeout(Box::pin(idle(comp, score)), duration, 999)))
+    }
+
+    #[test]
+    fn race_timeout_lifts_the_fallback_value_when_the_future_never_resolves() {
+        let event = Rc::new(RefCell::new(stream::pending::<IoEvent>()));
+        let mut app = AppComposite::new(AppData { event: event });
+
+        let result: Result<Score, AppError> = task::block_on(app.init(watchdog, 0));
+
+        assert_eq!(Ok(999), result);
+    }
+}